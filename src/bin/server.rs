@@ -0,0 +1,14 @@
+//! Binary entry point that runs the server, listening on `DEFAULT_PORT`
+//! until `Ctrl+C` is received.
+
+use weather_tracker::{server, DEFAULT_PORT};
+
+use tokio::net::TcpListener;
+use tokio::signal;
+
+#[tokio::main]
+async fn main() -> weather_tracker::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", DEFAULT_PORT)).await?;
+
+    server::run(listener, signal::ctrl_c()).await
+}
@@ -0,0 +1,68 @@
+use crate::parse::Parse;
+use crate::{Connection, Db, Frame};
+
+/// Get the value of key.
+///
+/// If the key does not exist the special value nil is returned. `Db` stores
+/// every value as a byte string, so there is no wrong-type error.
+#[derive(Debug)]
+pub struct Get {
+    /// Name of the key to get
+    key: String,
+}
+
+impl Get {
+    /// Create a new `Get` command which fetches `key`.
+    pub fn new(key: impl ToString) -> Get {
+        Get {
+            key: key.to_string(),
+        }
+    }
+
+    /// Get the key
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Parse a `Get` instance from a received frame.
+    ///
+    /// The `Parse` argument provides a cursor-like API to read fields from
+    /// the `Frame`. At this point, the entire frame has already been
+    /// received from the socket.
+    ///
+    /// The `GET` string has already been consumed.
+    ///
+    /// # Returns
+    ///
+    /// On success, the `Get` value is returned. If the frame is malformed,
+    /// `Err` is returned.
+    ///
+    /// # Format
+    ///
+    /// Expects an array frame containing two entries.
+    ///
+    /// ```text
+    /// GET key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Get> {
+        let key = parse.next_string()?;
+
+        Ok(Get { key })
+    }
+
+    /// Apply the `Get` command to the specified `Db` instance.
+    ///
+    /// The response is written to `dst`. This is called by the server in
+    /// order to execute a received command.
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = if let Some(value) = db.get(&self.key) {
+            Frame::Bulk(value)
+        } else {
+            Frame::Null
+        };
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
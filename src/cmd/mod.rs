@@ -1,6 +1,23 @@
 mod get;
 pub use get::Get;
 
+mod ping;
+pub use ping::Ping;
+
+mod publish;
+pub use publish::Publish;
+
+mod set;
+pub use set::Set;
+
+mod spec;
+
+mod subscribe;
+pub use subscribe::{Subscribe, Unsubscribe};
+
+mod throttle;
+pub use throttle::Throttle;
+
 mod unknown;
 pub use unknown::Unknown;
 
@@ -12,6 +29,12 @@ use crate::{db::Db, parse::Parse, shutdown::Shutdown, Connection, Frame};
 #[derive(Debug)]
 pub enum Command {
     Get(Get),
+    Ping(Ping),
+    Publish(Publish),
+    Set(Set),
+    Subscribe(Subscribe),
+    Throttle(Throttle),
+    Unsubscribe(Unsubscribe),
     Unknown(Unknown),
 }
 
@@ -32,29 +55,37 @@ impl Command {
         // result in an error being returned.
         let mut parse = Parse::new(frame)?;
 
+        // The arity check below needs the total number of entries in the
+        // frame, including the command name itself, so read it before
+        // anything is consumed.
+        let arg_count = parse.remaining() as i64;
+
         // All redis commands begin with command name as a string. The name
         // is read and converted to lower cases in order to do case sensitive
         // matching.
         let command_name = parse.next_string()?.to_lowercase();
 
-        // Match the command name, delegating the rest of the parsing to the
-        // specific command.
-        let command = match &command_name[..] {
-            "get" => Command::Get(Get::parse_frames(&mut parse)?),
-            _ => {
-                // The command is not recognized and an Unknown command is 
-                // returned. 
+        // Look the command up in the registry, delegating the rest of the
+        // parsing to the specific command's `CommandSpec::parse`.
+        let command = match spec::registry().get(&command_name[..]) {
+            Some(spec) => {
+                spec::check_arity(spec.name(), spec.arity(), arg_count)?;
+                spec.parse(&mut parse)?
+            }
+            None => {
+                // The command is not recognized and an Unknown command is
+                // returned.
                 //
-                // `return` is called here to skip the `finish()` call below. As 
-                // the command is not recognized, there is most likey 
-                // unconsumed fields remaining in the `Parse` instance. 
+                // `return` is called here to skip the `finish()` call below. As
+                // the command is not recognized, there is most likey
+                // unconsumed fields remaining in the `Parse` instance.
                 return Ok(Command::Unknown(Unknown::new(command_name)));
             }
         };
 
-        // Check if there is any remaining unconsumed fields in the `Parse` 
-        // value. If fields remain, this indicates an unexpected frame format 
-        // and an error is returned. 
+        // Check if there is any remaining unconsumed fields in the `Parse`
+        // value. If fields remain, this indicates an unexpected frame format
+        // and an error is returned.
         parse.finish()?;
 
         // The comamnd has been successfully parsed
@@ -69,12 +100,20 @@ impl Command {
         self,
         db: &Db,
         dst: &mut Connection,
-        _shutdown: Shutdown,
+        mut shutdown: Shutdown,
     ) -> crate::Result<()> {
         use Command::*;
 
         match self {
             Get(cmd) => cmd.apply(db, dst).await,
+            Ping(cmd) => cmd.apply(dst).await,
+            Publish(cmd) => cmd.apply(db, dst).await,
+            Set(cmd) => cmd.apply(db, dst).await,
+            Subscribe(cmd) => cmd.apply(db, dst, &mut shutdown, Vec::new()).await,
+            Throttle(cmd) => cmd.apply(db, dst).await,
+            // `Unsubscribe` cannot be applied on its own. It can only be
+            // received as part of the `Subscribe` loop.
+            Unsubscribe(_) => Err("`Unsubscribe` is unsupported in this context".into()),
             Unknown(cmd) => cmd.apply(dst).await,
         }
     }
@@ -83,6 +122,12 @@ impl Command {
     pub(crate) fn get_name(&self) -> &str {
         match self {
             Command::Get(_) => "get",
+            Command::Ping(_) => "ping",
+            Command::Publish(_) => "publish",
+            Command::Set(_) => "set",
+            Command::Subscribe(_) => "subscribe",
+            Command::Throttle(_) => "cl.throttle",
+            Command::Unsubscribe(_) => "unsubscribe",
             Command::Unknown(cmd) => cmd.get_name(),
         }
     }
@@ -0,0 +1,115 @@
+use crate::parse::{Parse, ParseError};
+use crate::{Connection, Db, Frame};
+
+use bytes::Bytes;
+use std::time::Duration;
+
+/// Set `key` to hold the string `value`.
+///
+/// If `key` already holds a value, it is overwritten, regardless of its
+/// type.
+#[derive(Debug)]
+pub struct Set {
+    /// the lookup key
+    key: String,
+
+    /// the value to be stored
+    value: Bytes,
+
+    /// When to expire the key
+    expire: Option<Duration>,
+}
+
+impl Set {
+    /// Create a new `Set` command which sets `key` to `value`.
+    ///
+    /// If `expire` is `Some`, the value should expire after the specified
+    /// duration.
+    pub fn new(key: impl ToString, value: Bytes, expire: Option<Duration>) -> Set {
+        Set {
+            key: key.to_string(),
+            value,
+            expire,
+        }
+    }
+
+    /// Get the key
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Get the value
+    pub fn value(&self) -> &Bytes {
+        &self.value
+    }
+
+    /// Get the expire
+    pub fn expire(&self) -> Option<Duration> {
+        self.expire
+    }
+
+    /// Parse a `Set` instance from a received frame.
+    ///
+    /// The `Parse` argument provides a cursor-like API to read fields from
+    /// the `Frame`. At this point, the entire frame has already been
+    /// received from the socket.
+    ///
+    /// The `SET` string has already been consumed.
+    ///
+    /// # Returns
+    ///
+    /// On success, the `Set` value is returned. If the frame is malformed,
+    /// `Err` is returned.
+    ///
+    /// # Format
+    ///
+    /// Expects an array frame containing at least 3 entries.
+    ///
+    /// ```text
+    /// SET key value [EX seconds | PX milliseconds]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Set> {
+        let key = parse.next_string()?;
+        let value = parse.next_bytes()?;
+        let mut expire = None;
+
+        // Attempt to parse an optional expiration. If none of `EX`/`PX`
+        // follows, the entry never expires.
+        match parse.next_string() {
+            Ok(s) if s.to_uppercase() == "EX" => {
+                let secs = parse.next_int()?;
+                expire = Some(Duration::from_secs(secs));
+            }
+            Ok(s) if s.to_uppercase() == "PX" => {
+                let ms = parse.next_int()?;
+                expire = Some(Duration::from_millis(ms));
+            }
+            // Currently, mini-redis does not support any of the other SET
+            // options. An error here makes `Command::from_frame` fail; the
+            // connection handler answers it with an error reply and goes on
+            // with the connection's other commands.
+            Ok(_) => return Err("currently `SET` only supports the expiration option".into()),
+            // The `EndOfStream` error indicates there is no further data to
+            // parse. In this case, it is a normal runtime situation and
+            // indicates there are no specified `SET` options.
+            Err(ParseError::EndOfStream) => {}
+            // All other errors are bubbled up to `Command::from_frame`.
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(Set { key, value, expire })
+    }
+
+    /// Apply the `Set` command to the specified `Db` instance.
+    ///
+    /// The response is written to `dst`. This is called by the server in
+    /// order to execute a received command.
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        db.set(self.key, self.value, self.expire);
+
+        let response = Frame::Simple("OK".to_string());
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use crate::cmd::{Command, Get, Ping, Publish, Set, Subscribe, Throttle, Unsubscribe};
+use crate::parse::Parse;
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Static metadata describing a single Redis command.
+///
+/// Mirrors what real Redis exposes via `COMMAND INFO`: the command's name,
+/// its arity, whether it may mutate the keyspace, and which arguments are
+/// keys. `from_frame` consults this metadata to validate arity before
+/// handing the remaining arguments to the command's own parser, so adding a
+/// new command only means registering a spec rather than growing a
+/// hand-written `match`.
+pub(crate) trait CommandSpec: Sync {
+    /// The command's name, lowercased.
+    fn name(&self) -> &'static str;
+
+    /// A positive value is the exact number of arguments the command takes,
+    /// including the command name itself. A negative value is the minimum
+    /// number of arguments, allowing for a variable-length tail.
+    fn arity(&self) -> i64;
+
+    /// `true` if applying the command may mutate the keyspace.
+    fn is_write(&self) -> bool;
+
+    /// The position (1-indexed) of the command's first key argument, or `0`
+    /// if the command has no key arguments.
+    fn first_key(&self) -> i64;
+
+    /// The step between successive key arguments, starting at `first_key`.
+    fn key_step(&self) -> i64;
+
+    /// Parse the command's arguments from `parse` into a `Command`.
+    ///
+    /// The command name has already been consumed from `parse`.
+    fn parse(&self, parse: &mut Parse) -> crate::Result<Command>;
+}
+
+/// Declares a zero-sized `CommandSpec` implementation for a command variant.
+macro_rules! spec {
+    (
+        $spec_name:ident,
+        name: $name:expr,
+        arity: $arity:expr,
+        write: $write:expr,
+        first_key: $first_key:expr,
+        key_step: $key_step:expr,
+        variant: $variant:ident,
+        cmd: $cmd:ty,
+    ) => {
+        struct $spec_name;
+
+        impl CommandSpec for $spec_name {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn arity(&self) -> i64 {
+                $arity
+            }
+
+            fn is_write(&self) -> bool {
+                $write
+            }
+
+            fn first_key(&self) -> i64 {
+                $first_key
+            }
+
+            fn key_step(&self) -> i64 {
+                $key_step
+            }
+
+            fn parse(&self, parse: &mut Parse) -> crate::Result<Command> {
+                Ok(Command::$variant(<$cmd>::parse_frames(parse)?))
+            }
+        }
+    };
+}
+
+spec!(
+    GetSpec,
+    name: "get",
+    arity: 2,
+    write: false,
+    first_key: 1,
+    key_step: 1,
+    variant: Get,
+    cmd: Get,
+);
+
+spec!(
+    SetSpec,
+    name: "set",
+    arity: -3,
+    write: true,
+    first_key: 1,
+    key_step: 1,
+    variant: Set,
+    cmd: Set,
+);
+
+spec!(
+    PingSpec,
+    name: "ping",
+    arity: -1,
+    write: false,
+    first_key: 0,
+    key_step: 0,
+    variant: Ping,
+    cmd: Ping,
+);
+
+spec!(
+    PublishSpec,
+    name: "publish",
+    arity: 3,
+    write: false,
+    first_key: 0,
+    key_step: 0,
+    variant: Publish,
+    cmd: Publish,
+);
+
+spec!(
+    SubscribeSpec,
+    name: "subscribe",
+    arity: -2,
+    write: false,
+    first_key: 0,
+    key_step: 0,
+    variant: Subscribe,
+    cmd: Subscribe,
+);
+
+spec!(
+    UnsubscribeSpec,
+    name: "unsubscribe",
+    arity: -1,
+    write: false,
+    first_key: 0,
+    key_step: 0,
+    variant: Unsubscribe,
+    cmd: Unsubscribe,
+);
+
+spec!(
+    ThrottleSpec,
+    name: "cl.throttle",
+    arity: -5,
+    write: true,
+    first_key: 1,
+    key_step: 1,
+    variant: Throttle,
+    cmd: Throttle,
+);
+
+/// Returns the full command registry, keyed by lowercased command name.
+pub(crate) fn registry() -> &'static HashMap<&'static str, &'static dyn CommandSpec> {
+    static REGISTRY: OnceLock<HashMap<&'static str, &'static dyn CommandSpec>> = OnceLock::new();
+
+    REGISTRY.get_or_init(|| {
+        let specs: Vec<&'static dyn CommandSpec> = vec![
+            &GetSpec,
+            &PingSpec,
+            &SetSpec,
+            &PublishSpec,
+            &SubscribeSpec,
+            &UnsubscribeSpec,
+            &ThrottleSpec,
+        ];
+
+        specs.into_iter().map(|spec| (spec.name(), spec)).collect()
+    })
+}
+
+/// Checks `actual` (the number of entries in the received frame, including
+/// the command name) against `arity`, per the `CommandSpec::arity` contract.
+pub(crate) fn check_arity(name: &str, arity: i64, actual: i64) -> crate::Result<()> {
+    let satisfied = if arity >= 0 {
+        actual == arity
+    } else {
+        actual >= -arity
+    };
+
+    if satisfied {
+        Ok(())
+    } else {
+        Err(format!("ERR wrong number of arguments for '{}' command", name).into())
+    }
+}
@@ -0,0 +1,320 @@
+use crate::cmd::Unknown;
+use crate::parse::{Parse, ParseError};
+use crate::{Command, Connection, Db, Frame, Shutdown};
+
+use bytes::Bytes;
+use std::pin::Pin;
+use tokio::select;
+use tokio_stream::{Stream, StreamExt, StreamMap};
+
+/// Subscribes the client to one or more channels.
+///
+/// Once the client enters the subscribed state, it is not supposed to issue
+/// any other commands, except for additional SUBSCRIBE, PSUBSCRIBE,
+/// UNSUBSCRIBE, PUNSUBSCRIBE, PING, and QUIT commands.
+#[derive(Debug)]
+pub struct Subscribe {
+    channels: Vec<String>,
+}
+
+/// Unsubscribes the client from one or more channels.
+///
+/// When no channels are supplied, unsubscribes from all channels.
+#[derive(Debug)]
+pub struct Unsubscribe {
+    channels: Vec<String>,
+}
+
+impl Subscribe {
+    /// Creates a new `Subscribe` command to listen on the specified channels.
+    pub fn new(channels: Vec<String>) -> Subscribe {
+        Subscribe { channels }
+    }
+
+    /// Parse a `Subscribe` instance from a received frame.
+    ///
+    /// The `Parse` argument provides a cursor-like API to read fields from
+    /// the `Frame`. At this point, the entire frame has already been
+    /// received from the socket.
+    ///
+    /// The `SUBSCRIBE` string has already been consumed.
+    ///
+    /// # Format
+    ///
+    /// Expects an array frame containing two or more entries.
+    ///
+    /// ```text
+    /// SUBSCRIBE channel [channel ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Subscribe> {
+        // The `SUBSCRIBE` string has already been consumed. At least one
+        // channel name remains.
+        let mut channels = vec![parse.next_string()?];
+
+        // Now, the remainder of the frame is consumed. Each value must be a
+        // string, or the frame is malformed.
+        loop {
+            match parse.next_string() {
+                // A string has been consumed from the `parse`, push it into
+                // the list of channels to subscribe to.
+                Ok(s) => channels.push(s),
+                // The `EndOfStream` error indicates there is no further data
+                // to parse.
+                Err(ParseError::EndOfStream) => break,
+                // All other errors are bubbled up to `Command::from_frame`.
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Subscribe { channels })
+    }
+
+    /// Apply the `Subscribe` command to the specified `Db` instance.
+    ///
+    /// This function is the entry point and includes the initial list of
+    /// channels to subscribe to. Additional `subscribe` and `unsubscribe`
+    /// commands may be received from the client and the list of subscriptions
+    /// are updated accordingly.
+    ///
+    /// `pending` holds any frames that arrived in the same pipelined batch as
+    /// this `SUBSCRIBE` but have not yet been applied. Since this function
+    /// does not return for as long as the client stays subscribed, those
+    /// frames would otherwise be stranded in the caller's batch and silently
+    /// dropped; they are processed here, in order, before falling back to
+    /// reading further frames directly off `dst`.
+    ///
+    /// [here]: https://redis.io/topics/pubsub
+    pub(crate) async fn apply(
+        mut self,
+        db: &Db,
+        dst: &mut Connection,
+        shutdown: &mut Shutdown,
+        pending: Vec<Frame>,
+    ) -> crate::Result<()> {
+        // Each individual channel subscription is handled using a
+        // `sync::broadcast` channel. Messages are then forwarded to the
+        // connection over a `StreamMap`.
+        //
+        // New channels may be subscribed to and unsubscribed from as this
+        // command runs. The `StreamMap` is used to track active
+        // subscriptions. The `StreamMap` merges messages from individual
+        // broadcast channels as they are received.
+        let mut subscriptions = StreamMap::new();
+
+        // Subscribe to the channels named by this `SUBSCRIBE` before
+        // processing anything else, so that per-channel confirmations are
+        // sent in the order the client issued them.
+        for channel_name in self.channels.drain(..) {
+            subscribe_to_channel(channel_name, &mut subscriptions, db, dst).await?;
+        }
+
+        // Drain any commands left over from the pipelined batch that handed
+        // this `SUBSCRIBE` off before it can fall back to reading live from
+        // the socket.
+        for frame in pending {
+            handle_command(frame, &mut self.channels, &mut subscriptions, dst).await?;
+
+            // A pipelined `SUBSCRIBE` takes effect before the next pending
+            // frame is handled, so a later `UNSUBSCRIBE` in the batch sees it.
+            for channel_name in self.channels.drain(..) {
+                subscribe_to_channel(channel_name, &mut subscriptions, db, dst).await?;
+            }
+        }
+
+        loop {
+            // `self.channels` is used to track additional channels to
+            // subscribe to. When new `SUBSCRIBE` commands are received
+            // during the execution of `apply`, the new channels are pushed
+            // onto this vec.
+            for channel_name in self.channels.drain(..) {
+                subscribe_to_channel(channel_name, &mut subscriptions, db, dst).await?;
+            }
+
+            // Wait for one of the following to happen:
+            //
+            // - Receive a message from one of the subscribed channels.
+            // - Receive a subscribe or unsubscribe command from the client.
+            // - A server shutdown signal.
+            select! {
+                // Receive messages from subscribed channels
+                Some((channel_name, msg)) = subscriptions.next() => {
+                    dst.write_frame(&make_message_frame(channel_name, msg)).await?;
+                    dst.flush().await?;
+                }
+                res = dst.read_frame() => {
+                    let frame = match res? {
+                        Some(frame) => frame,
+                        // This happens if the remote client has disconnected.
+                        None => return Ok(())
+                    };
+
+                    handle_command(
+                        frame,
+                        &mut self.channels,
+                        &mut subscriptions,
+                        dst,
+                    ).await?;
+                }
+                _ = shutdown.recv() => {
+                    return Ok(());
+                }
+            };
+        }
+    }
+}
+
+/// The "channel" over which messages are received.
+type Messages = Pin<Box<dyn Stream<Item = Bytes> + Send>>;
+
+async fn subscribe_to_channel(
+    channel_name: String,
+    subscriptions: &mut StreamMap<String, Messages>,
+    db: &Db,
+    dst: &mut Connection,
+) -> crate::Result<()> {
+    let mut rx = db.subscribe(channel_name.clone());
+
+    // Subscribe to the channel.
+    let rx = Box::pin(async_stream::stream! {
+        loop {
+            match rx.recv().await {
+                Ok(msg) => yield msg,
+                // If we lagged in consuming messages, just resume.
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
+    });
+
+    // Track subscription in this client's subscription set.
+    subscriptions.insert(channel_name.clone(), rx);
+
+    // Respond with the confirmation of subscription to the channel.
+    let response = make_subscribe_frame(channel_name, subscriptions.len());
+    dst.write_frame(&response).await?;
+    dst.flush().await?;
+
+    Ok(())
+}
+
+/// Handle a command received while inside `Subscribe::apply`. Only
+/// subscribe and unsubscribe commands are permitted in this context.
+///
+/// Any new subscriptions are appended to `subscribe_to` instead of modifying
+/// `subscriptions` directly. The caller is responsible for performing the
+/// actual subscribe work; unsubscribes are applied here directly.
+async fn handle_command(
+    frame: Frame,
+    subscribe_to: &mut Vec<String>,
+    subscriptions: &mut StreamMap<String, Messages>,
+    dst: &mut Connection,
+) -> crate::Result<()> {
+    // A command has been received from the client.
+    //
+    // Only `SUBSCRIBE` and `UNSUBSCRIBE` commands are permitted in this
+    // context.
+    match Command::from_frame(frame)? {
+        Command::Subscribe(subscribe) => {
+            // The `apply` method will subscribe to the channels we add to
+            // this vector.
+            subscribe_to.extend(subscribe.channels);
+        }
+        Command::Unsubscribe(mut unsubscribe) => {
+            // If no channels are specified, this requests unsubscribing from
+            // **all** channels. To implement this, the `unsubscribe.channels`
+            // vec is populated with the list of channels currently
+            // subscribed to.
+            if unsubscribe.channels.is_empty() {
+                unsubscribe.channels = subscriptions.keys().map(|k| k.to_string()).collect();
+            }
+
+            for channel_name in unsubscribe.channels {
+                subscriptions.remove(&channel_name);
+
+                let response = make_unsubscribe_frame(channel_name, subscriptions.len());
+                dst.write_frame(&response).await?;
+                dst.flush().await?;
+            }
+        }
+        command => {
+            let cmd = Unknown::new(command.get_name());
+            cmd.apply(dst).await?;
+            dst.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates the response to a subscribe request.
+///
+/// All of these functions take the `channel_name` as a `String` instead of
+/// a `&str` since `Bytes::from` can reuse the allocation in the `String`, and
+/// taking a `&str` would require copying the data. This allows the caller to
+/// decide whether to clone the channel name or not.
+fn make_subscribe_frame(channel_name: String, num_subs: usize) -> Frame {
+    let mut response = Frame::array();
+    response.push_bulk(Bytes::from("subscribe".as_bytes()));
+    response.push_bulk(Bytes::from(channel_name.into_bytes()));
+    response.push_int(num_subs as i64);
+    response
+}
+
+/// Creates the response to an unsubscribe request.
+fn make_unsubscribe_frame(channel_name: String, num_subs: usize) -> Frame {
+    let mut response = Frame::array();
+    response.push_bulk(Bytes::from("unsubscribe".as_bytes()));
+    response.push_bulk(Bytes::from(channel_name.into_bytes()));
+    response.push_int(num_subs as i64);
+    response
+}
+
+/// Creates a message informing the client about a new message on a channel
+/// that the client subscribes to.
+fn make_message_frame(channel_name: String, msg: Bytes) -> Frame {
+    let mut response = Frame::array();
+    response.push_bulk(Bytes::from("message".as_bytes()));
+    response.push_bulk(Bytes::from(channel_name.into_bytes()));
+    response.push_bulk(msg);
+    response
+}
+
+impl Unsubscribe {
+    /// Create a new `Unsubscribe` command with the given `channels`.
+    pub fn new(channels: &[String]) -> Unsubscribe {
+        Unsubscribe {
+            channels: channels.to_vec(),
+        }
+    }
+
+    /// Parse a `Unsubscribe` instance from a received frame.
+    ///
+    /// The `Parse` argument provides a cursor-like API to read fields from
+    /// the `Frame`. At this point, the entire frame has already been
+    /// received from the socket.
+    ///
+    /// The `UNSUBSCRIBE` string has already been consumed.
+    ///
+    /// # Format
+    ///
+    /// Expects an array frame containing at least one entry.
+    ///
+    /// ```text
+    /// UNSUBSCRIBE [channel [channel ...]]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Unsubscribe> {
+        let mut channels = vec![];
+
+        // Each entry in the frame must be a string or the frame is
+        // malformed. Once all values in the frame have been consumed, the
+        // command is fully parsed.
+        loop {
+            match parse.next_string() {
+                Ok(s) => channels.push(s),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Unsubscribe { channels })
+    }
+}
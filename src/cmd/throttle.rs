@@ -0,0 +1,117 @@
+use crate::parse::{Parse, ParseError};
+use crate::{Connection, Db, Frame};
+
+/// Rate limit a key using the Generic Cell Rate Algorithm (GCRA).
+///
+/// `CL.THROTTLE` lets a client enforce a rate limit without running a
+/// separate rate-limiting service: the limit state lives in `Db` alongside
+/// ordinary keys.
+#[derive(Debug)]
+pub struct Throttle {
+    /// The key identifying the thing being rate limited.
+    key: String,
+
+    /// The maximum burst size, i.e. the number of requests that can be made
+    /// in excess of the steady rate before being limited.
+    max_burst: u64,
+
+    /// The number of requests allowed per `period` (in seconds).
+    count_per_period: u64,
+
+    /// The rate-limiting period, in seconds.
+    period: u64,
+
+    /// The number of units this request consumes. Defaults to `1`.
+    quantity: u64,
+}
+
+impl Throttle {
+    /// Create a new `Throttle` command.
+    pub fn new(
+        key: impl ToString,
+        max_burst: u64,
+        count_per_period: u64,
+        period: u64,
+        quantity: u64,
+    ) -> Throttle {
+        Throttle {
+            key: key.to_string(),
+            max_burst,
+            count_per_period,
+            period,
+            quantity,
+        }
+    }
+
+    /// Parse a `Throttle` instance from a received frame.
+    ///
+    /// The `Parse` argument provides a cursor-like API to read fields from
+    /// the `Frame`. At this point, the entire frame has already been
+    /// received from the socket.
+    ///
+    /// The `CL.THROTTLE` string has already been consumed.
+    ///
+    /// # Format
+    ///
+    /// Expects an array frame containing five or six entries.
+    ///
+    /// ```text
+    /// CL.THROTTLE key max_burst count_per_period period [quantity]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Throttle> {
+        let key = parse.next_string()?;
+        let max_burst = parse.next_int()?;
+        let count_per_period = parse.next_int()?;
+        let period = parse.next_int()?;
+
+        let quantity = match parse.next_int() {
+            Ok(quantity) => quantity,
+            Err(ParseError::EndOfStream) => 1,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Throttle {
+            key,
+            max_burst,
+            count_per_period,
+            period,
+            quantity,
+        })
+    }
+
+    /// Apply the `Throttle` command to the specified `Db` instance.
+    ///
+    /// The response is written to `dst`. This is called by the server in
+    /// order to execute a received command.
+    ///
+    /// An invalid `count_per_period` or `max_burst` is reported back to the
+    /// client as an error reply rather than terminating the connection,
+    /// since it reflects bad arguments rather than a protocol violation.
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let result = match db.throttle(
+            &self.key,
+            self.max_burst,
+            self.count_per_period,
+            self.period,
+            self.quantity,
+        ) {
+            Ok(result) => result,
+            Err(err) => {
+                let response = Frame::Error(format!("ERR {err}"));
+                dst.write_frame(&response).await?;
+                return Ok(());
+            }
+        };
+
+        let mut response = Frame::array();
+        response.push_int(result.limited as i64);
+        response.push_int(result.limit);
+        response.push_int(result.remaining);
+        response.push_int(result.retry_after);
+        response.push_int(result.reset_after);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
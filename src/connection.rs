@@ -0,0 +1,197 @@
+use crate::frame::{self, Frame};
+
+use bytes::{Buf, BytesMut};
+use std::io::{self, Cursor};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use tokio::net::TcpStream;
+
+/// Send and receive `Frame` values from a remote peer.
+///
+/// When implementing networking protocols, a message on that protocol is
+/// often composed of several smaller messages known as frames. The purpose of
+/// `Connection` is to read and write frames on the underlying `TcpStream`.
+///
+/// To read frames, `Connection` uses an internal buffer, which is filled up
+/// until there are enough bytes to create a full frame. Once this happens,
+/// the `Connection` creates the frame and returns it to the caller.
+///
+/// When sending frames, the frame is first encoded into the write buffer.
+/// The contents of the write buffer are then written to the socket.
+#[derive(Debug)]
+pub struct Connection {
+    /// The `TcpStream`. It is decorated with a `BufWriter`, which provides
+    /// write level buffering.
+    stream: BufWriter<TcpStream>,
+
+    /// The buffer for reading frames.
+    buffer: BytesMut,
+}
+
+impl Connection {
+    /// Create a new `Connection`, backed by `socket`.
+    pub fn new(socket: TcpStream) -> Connection {
+        Connection {
+            stream: BufWriter::new(socket),
+            buffer: BytesMut::with_capacity(4 * 1024),
+        }
+    }
+
+    /// Read a single `Frame` value from the underlying stream.
+    ///
+    /// The function waits until it has retrieved enough data to parse a
+    /// frame. Any data remaining in the read buffer after the frame has been
+    /// parsed is kept there for the next call to `read_frame`.
+    ///
+    /// # Returns
+    ///
+    /// On success, the received frame is returned. If the `TcpStream` is
+    /// closed in a way that doesn't break a frame in half, it returns `None`.
+    /// Otherwise, an error is returned.
+    pub async fn read_frame(&mut self) -> crate::Result<Option<Frame>> {
+        loop {
+            if let Some(frame) = self.parse_frame()? {
+                return Ok(Some(frame));
+            }
+
+            if 0 == self.stream.read_buf(&mut self.buffer).await? {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                } else {
+                    return Err("connection reset by peer".into());
+                }
+            }
+        }
+    }
+
+    /// Read a single `Frame`, then drain any additional frames that are
+    /// already fully buffered, without performing further reads from the
+    /// socket.
+    ///
+    /// Clients commonly pipeline several commands back-to-back without
+    /// waiting for each reply; when that happens, a single `read_buf` call
+    /// can land more than one frame in `self.buffer`. Returning them all
+    /// together lets the caller apply them in order and reply once, rather
+    /// than processing them one read-frame roundtrip at a time.
+    ///
+    /// # Returns
+    ///
+    /// On success, the received frames are returned, in arrival order. If
+    /// the `TcpStream` is closed before any frame is received, `None` is
+    /// returned.
+    pub async fn read_frame_batch(&mut self) -> crate::Result<Option<Vec<Frame>>> {
+        let first = match self.read_frame().await? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        let mut frames = vec![first];
+
+        while let Some(frame) = self.parse_frame()? {
+            frames.push(frame);
+        }
+
+        Ok(Some(frames))
+    }
+
+    /// Tries to parse a frame from the buffer. If the buffer contains enough
+    /// data, the frame is returned and the data removed from the buffer. If
+    /// not enough data has been buffered yet, `Ok(None)` is returned.
+    fn parse_frame(&mut self) -> crate::Result<Option<Frame>> {
+        let mut buf = Cursor::new(&self.buffer[..]);
+
+        match Frame::check(&mut buf) {
+            Ok(()) => {
+                let len = buf.position() as usize;
+
+                buf.set_position(0);
+
+                let frame = Frame::parse(&mut buf)?;
+
+                self.buffer.advance(len);
+
+                Ok(Some(frame))
+            }
+            Err(frame::Error::Incomplete) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write a single `Frame` value to the underlying stream.
+    ///
+    /// This only buffers the encoded frame; call [`Connection::flush`]
+    /// afterwards to ensure it reaches the peer. Buffering without an
+    /// implicit flush lets a caller write several replies (e.g. a pipeline
+    /// of commands) and flush them as a single write to the socket.
+    pub async fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        match frame {
+            Frame::Array(val) => {
+                self.stream.write_u8(b'*').await?;
+                self.write_decimal(val.len() as i64).await?;
+
+                for entry in &**val {
+                    self.write_value(entry).await?;
+                }
+            }
+            _ => self.write_value(frame).await?,
+        }
+
+        Ok(())
+    }
+
+    /// Flush any buffered data to the underlying stream.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush().await
+    }
+
+    /// Write a frame literal to the stream
+    async fn write_value(&mut self, frame: &Frame) -> io::Result<()> {
+        match frame {
+            Frame::Simple(val) => {
+                self.stream.write_u8(b'+').await?;
+                self.stream.write_all(val.as_bytes()).await?;
+                self.stream.write_all(b"\r\n").await?;
+            }
+            Frame::Error(val) => {
+                self.stream.write_u8(b'-').await?;
+                self.stream.write_all(val.as_bytes()).await?;
+                self.stream.write_all(b"\r\n").await?;
+            }
+            Frame::Integer(val) => {
+                self.stream.write_u8(b':').await?;
+                self.write_decimal(*val).await?;
+            }
+            Frame::Null => {
+                self.stream.write_all(b"$-1\r\n").await?;
+            }
+            Frame::Bulk(val) => {
+                let len = val.len();
+
+                self.stream.write_u8(b'$').await?;
+                self.write_decimal(len as i64).await?;
+                self.stream.write_all(val).await?;
+                self.stream.write_all(b"\r\n").await?;
+            }
+            // Encoding an `Array` from within a value cannot be done using a
+            // recursive strategy. In general, this is not encountered as
+            // arrays are not nested in practice.
+            Frame::Array(_val) => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Write a decimal frame to the stream
+    async fn write_decimal(&mut self, val: i64) -> io::Result<()> {
+        use std::io::Write;
+
+        let mut buf = [0u8; 20];
+        let mut buf = Cursor::new(&mut buf[..]);
+        write!(&mut buf, "{}", val)?;
+
+        let pos = buf.position() as usize;
+        self.stream.write_all(&buf.get_ref()[..pos]).await?;
+        self.stream.write_all(b"\r\n").await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,508 @@
+use bytes::Bytes;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Notify};
+use tokio::time::{self, Instant as TokioInstant};
+
+/// The outcome of a `CL.THROTTLE` rate-limit check, computed by
+/// [`Db::throttle`] using the Generic Cell Rate Algorithm.
+#[derive(Debug, Clone, Copy)]
+pub struct ThrottleResult {
+    /// `true` if the request exceeds the rate limit and must be rejected.
+    pub limited: bool,
+
+    /// The effective limit, i.e. `max_burst + 1`.
+    pub limit: i64,
+
+    /// The number of requests remaining in the current burst.
+    pub remaining: i64,
+
+    /// Seconds to wait before retrying, or `-1` if the request was allowed.
+    pub retry_after: i64,
+
+    /// Seconds until the limit fully resets.
+    pub reset_after: i64,
+}
+
+/// Returns a monotonic clock reading, in fractional seconds, relative to an
+/// arbitrary fixed point established the first time this is called.
+///
+/// `f64` seconds (rather than `Instant`) are used so the GCRA arithmetic in
+/// [`Db::throttle`] stays exact and easy to reason about.
+fn monotonic_seconds() -> f64 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    let epoch = *EPOCH.get_or_init(Instant::now);
+    Instant::now().duration_since(epoch).as_secs_f64()
+}
+
+/// A wrapper around a `Db` instance. This exists to allow orderly cleanup
+/// of the `Db` by signalling the background purge task to shut down when
+/// this struct is dropped.
+#[derive(Debug)]
+pub(crate) struct DbDropGuard {
+    /// The `Db` instance that will be shut down when this `DbDropGuard` struct
+    /// is dropped.
+    db: Db,
+}
+
+impl DbDropGuard {
+    /// Create a new `DbDropGuard`, wrapping a `Db` instance. When this is
+    /// dropped, the `Db`'s purge task will be shut down.
+    pub(crate) fn new() -> DbDropGuard {
+        DbDropGuard { db: Db::new() }
+    }
+
+    /// Get the shared database. Internally, this is an `Arc`, so a clone
+    /// only increments the ref count.
+    pub(crate) fn db(&self) -> Db {
+        self.db.clone()
+    }
+}
+
+impl Drop for DbDropGuard {
+    fn drop(&mut self) {
+        // Signal the `Db` instance to shut down the task that purges expired
+        // keys.
+        self.db.shutdown_purge_task();
+    }
+}
+
+/// Server state shared across all connections.
+///
+/// `Db` contains a `HashMap` storing the key/value data and all
+/// `broadcast::Sender` values for active pub/sub channels.
+///
+/// A `Db` instance is a handle to shared state. Cloning `Db` is shallow and
+/// only incurs an atomic ref count increment.
+///
+/// When a `Db` value is created, a background task is spawned. This task is
+/// used to expire values after the requested duration has elapsed. The task
+/// runs until it is signalled to shut down, which `DbDropGuard` does when it
+/// is dropped; a `Db` created without a guard keeps its task running.
+#[derive(Debug, Clone)]
+pub struct Db {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    /// The shared state is guarded by a mutex. This is a `std::sync::Mutex`
+    /// and not a Tokio mutex. This is because there are no asynchronous
+    /// operations being performed while holding the mutex. Additionally, the
+    /// critical sections are very small.
+    state: Mutex<State>,
+
+    /// Notifies the background task handling entry expiration. The
+    /// background task waits on this to be notified, then checks for
+    /// expired values or the shutdown signal.
+    background_task: Notify,
+}
+
+#[derive(Debug)]
+struct State {
+    /// The key-value data. We are not trying to do anything fancy so a
+    /// `std::collections::HashMap` works fine.
+    entries: HashMap<String, Entry>,
+
+    /// Tracks key TTLs.
+    ///
+    /// A `BTreeSet` is used to maintain expirations sorted by when they
+    /// expire. This allows the background task to iterate this map to find
+    /// the value expiring next.
+    ///
+    /// While highly unlikely, it is possible for more than one expiration to
+    /// be created for the same instant. Because of this, the `Instant` is
+    /// insufficient for the key. The entry's key (`String`) is used to break
+    /// these ties.
+    expirations: BTreeSet<(Instant, String)>,
+
+    /// The pub/sub channels.
+    ///
+    /// The `Db` instance does not know anything about the semantics of
+    /// channel names. An arbitrary string key can be used. When a value is
+    /// published on a channel, a `broadcast::Sender` is used to fan the
+    /// value out to any connected subscribers.
+    pub_sub: HashMap<String, broadcast::Sender<Bytes>>,
+
+    /// True when the `Db` instance is shutting down. This happens when the
+    /// `DbDropGuard` drops. Setting this to `true` signals to the background
+    /// task to exit.
+    shutdown: bool,
+}
+
+/// Entry in the key-value store
+#[derive(Debug)]
+struct Entry {
+    /// Stored data
+    data: Bytes,
+
+    /// Instant at which the entry expires and should be removed from the
+    /// database.
+    expires_at: Option<Instant>,
+}
+
+impl Db {
+    /// Create a new, empty, `Db` instance. Allocates shared state and spawns
+    /// a background task to manage key expiration.
+    pub fn new() -> Db {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                entries: HashMap::new(),
+                expirations: BTreeSet::new(),
+                pub_sub: HashMap::new(),
+                shutdown: false,
+            }),
+            background_task: Notify::new(),
+        });
+
+        // Start the background task.
+        tokio::spawn(purge_expired_tasks(shared.clone()));
+
+        Db { shared }
+    }
+
+    /// Get the value associated with a key.
+    ///
+    /// Returns `None` if there is no value associated with the key. This may
+    /// be due to never having assigned a value to the key or a previously
+    /// assigned value expired.
+    pub fn get(&self, key: &str) -> Option<Bytes> {
+        let state = self.shared.state.lock().unwrap();
+        state.entries.get(key).map(|entry| entry.data.clone())
+    }
+
+    /// Set the value associated with a key along with an optional expiration
+    /// duration.
+    ///
+    /// If a value is already associated with the key, it is removed.
+    pub fn set(&self, key: String, value: Bytes, expire: Option<Duration>) {
+        let mut state = self.shared.state.lock().unwrap();
+        let notify = Db::set_locked(&mut state, key, value, expire);
+
+        // Release the mutex before notifying the background task. This
+        // helps reduce contention by avoiding the background task waking up
+        // only to be unable to acquire the mutex due to this function still
+        // holding it.
+        drop(state);
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+    }
+
+    /// Insert `key`/`value` into `state`, which the caller already holds
+    /// locked. Returns `true` if the background task should be notified
+    /// because this changed the next expiration.
+    ///
+    /// Factored out of `set` so that `throttle` can perform its own
+    /// conditional write under the same critical section it used to read
+    /// the previous value, rather than re-locking in between.
+    fn set_locked(state: &mut State, key: String, value: Bytes, expire: Option<Duration>) -> bool {
+        // If this `set` becomes the key that expires **next**, the
+        // background task needs to be notified so it can update its state.
+        let mut notify = false;
+
+        let expires_at = expire.map(|duration| {
+            let when = Instant::now() + duration;
+
+            // Only notify the worker task if the newly inserted expiration
+            // is the next one to evict. In this case, the worker needs to be
+            // woken up to update its state.
+            notify = state
+                .next_expiration()
+                .map(|expiration| expiration > when)
+                .unwrap_or(true);
+
+            when
+        });
+
+        let prev = state.entries.insert(
+            key.clone(),
+            Entry {
+                data: value,
+                expires_at,
+            },
+        );
+
+        // If there was a value previously associated with the key **and** it
+        // had an expiration time, the associated entry in the `expirations`
+        // map must also be removed. This avoids leaking data.
+        if let Some(prev) = prev {
+            if let Some(when) = prev.expires_at {
+                state.expirations.remove(&(when, key.clone()));
+            }
+        }
+
+        // Track the expiration.
+        if let Some(when) = expires_at {
+            state.expirations.insert((when, key));
+        }
+
+        notify
+    }
+
+    /// Rate limit `key` using the Generic Cell Rate Algorithm.
+    ///
+    /// The "theoretical arrival time" (`tat`) tracked by the algorithm is
+    /// stored as an ordinary value in the same key space as `get`/`set`, with
+    /// its expiration driving cleanup, so no separate storage is needed.
+    ///
+    /// The read of the previous `tat` and the conditional write of the new
+    /// one happen under a single lock acquisition, so concurrent calls for
+    /// the same `key` cannot interleave and bypass the limit.
+    ///
+    /// Returns `Err` if `count_per_period` is `0` (which would divide by
+    /// zero) or if `max_burst` is large enough that the effective limit
+    /// overflows, rather than trusting arbitrary client-supplied values.
+    pub fn throttle(
+        &self,
+        key: &str,
+        max_burst: u64,
+        count_per_period: u64,
+        period: u64,
+        quantity: u64,
+    ) -> crate::Result<ThrottleResult> {
+        if count_per_period == 0 {
+            return Err("count_per_period must be greater than 0".into());
+        }
+
+        let limit = max_burst
+            .checked_add(1)
+            .ok_or("max_burst is too large")?;
+
+        let emission_interval = period as f64 / count_per_period as f64;
+        let tau = emission_interval * limit as f64;
+
+        let now = monotonic_seconds();
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        let tat = state
+            .entries
+            .get(key)
+            .and_then(|entry| std::str::from_utf8(&entry.data).ok()?.parse::<f64>().ok())
+            .unwrap_or(now);
+
+        let increment = quantity as f64 * emission_interval;
+        let new_tat = tat.max(now) + increment;
+        let allow_at = new_tat - tau;
+        let reset_after = new_tat - now;
+        let remaining = ((tau - reset_after) / emission_interval).floor().max(0.0) as i64;
+
+        let limited = now < allow_at;
+
+        let mut notify = false;
+        let retry_after = if limited {
+            allow_at - now
+        } else {
+            let ttl = Duration::from_secs_f64(reset_after.ceil());
+            notify = Db::set_locked(
+                &mut state,
+                key.to_string(),
+                Bytes::from(new_tat.to_string()),
+                Some(ttl),
+            );
+            -1.0
+        };
+
+        drop(state);
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+
+        Ok(ThrottleResult {
+            limited,
+            limit: limit as i64,
+            remaining,
+            retry_after: retry_after.ceil() as i64,
+            reset_after: reset_after.ceil() as i64,
+        })
+    }
+
+    /// Returns a `Receiver` for the requested channel.
+    ///
+    /// The returned `Receiver` is used to receive values broadcast by `pub`
+    /// calls to the channel.
+    pub fn subscribe(&self, key: String) -> broadcast::Receiver<Bytes> {
+        use std::collections::hash_map::Entry;
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        // If there is no entry for the requested channel, then create a new
+        // broadcast channel and associate it with the key. If one already
+        // exists, return an associated receiver.
+        match state.pub_sub.entry(key) {
+            Entry::Occupied(e) => e.get().subscribe(),
+            Entry::Vacant(e) => {
+                // No broadcast channel exists yet, so create one.
+                //
+                // The channel is created with a capacity of `1024` messages.
+                // A message is stored in the channel until **all**
+                // subscribers have seen it. This means that a slow
+                // subscriber could result in messages being held
+                // indefinitely.
+                //
+                // When the channel's capacity fills up, publishing will
+                // result in old messages being dropped. This prevents slow
+                // consumers from blocking the entire system.
+                let (tx, rx) = broadcast::channel(1024);
+                e.insert(tx);
+                rx
+            }
+        }
+    }
+
+    /// Publish a message to the channel. Returns the number of subscribers
+    /// listening on the channel.
+    pub fn publish(&self, key: &str, value: Bytes) -> usize {
+        let state = self.shared.state.lock().unwrap();
+
+        state
+            .pub_sub
+            .get(key)
+            // On a successful message send on the broadcast channel, the
+            // number of subscribers is returned. An error indicates there
+            // are no receivers, in which case, `0` should be returned.
+            .map(|tx| tx.send(value).unwrap_or(0))
+            // If there is no entry for the channel key, then there are no
+            // subscribers. In this case, return `0`.
+            .unwrap_or(0)
+    }
+
+    /// Signals the purge background task to shut down. This is called by the
+    /// `DbDropGuard`s `Drop` implementation.
+    fn shutdown_purge_task(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.shutdown = true;
+
+        drop(state);
+        self.shared.background_task.notify_one();
+    }
+}
+
+impl Default for Db {
+    fn default() -> Self {
+        Db::new()
+    }
+}
+
+impl Shared {
+    /// Purge all expired keys and return the `Instant` at which the **next**
+    /// key will expire. The background task will sleep until this instant.
+    fn purge_expired_keys(&self) -> Option<Instant> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.shutdown {
+            // The database is shutting down: the `DbDropGuard` has dropped.
+            // The background task should exit.
+            return None;
+        }
+
+        // This is needed to make the borrow checker happy. Splitting `state`
+        // into the `&mut` reference to the HashMap and the `&mut` reference
+        // to the BTreeSet allows the two to be mutated independently.
+        let state = &mut *state;
+
+        let now = Instant::now();
+
+        while let Some(&(when, ref key)) = state.expirations.iter().next() {
+            if when > now {
+                // Done purging, `when` is the instant at which the next key
+                // expires. The worker task will wait until this instant.
+                return Some(when);
+            }
+
+            // The key expired, remove it.
+            state.entries.remove(key);
+            state.expirations.remove(&(when, key.clone()));
+        }
+
+        None
+    }
+
+    /// Returns `true` if the database is shutting down.
+    ///
+    /// The `shutdown` flag is set when the `DbDropGuard` has dropped,
+    /// indicating that the background purge task should exit.
+    fn is_shutdown(&self) -> bool {
+        self.state.lock().unwrap().shutdown
+    }
+}
+
+impl State {
+    fn next_expiration(&self) -> Option<Instant> {
+        self.expirations.iter().next().map(|expiration| expiration.0)
+    }
+}
+
+/// Routine executed by the background task.
+///
+/// Wait to be notified. On notification, purge any expired keys from the
+/// shared state handle. If `shutdown` is set, terminate the task.
+async fn purge_expired_tasks(shared: Arc<Shared>) {
+    // If the shutdown flag is set, then the task should exit.
+    while !shared.is_shutdown() {
+        // Purge all keys that are expired. The function returns the instant
+        // at which the **next** key will expire. The worker should wait
+        // until the instant has passed then purge again.
+        if let Some(when) = shared.purge_expired_keys() {
+            // Wait until the next key expires **or** until the background
+            // task is notified. If the task is notified, then it must
+            // reload its state as new keys have been set to expire early.
+            // This is done by looping.
+            tokio::select! {
+                _ = time::sleep_until(TokioInstant::from_std(when)) => {}
+                _ = shared.background_task.notified() => {}
+            }
+        } else {
+            // There are no keys expiring in the future. Wait until the task
+            // is notified.
+            shared.background_task.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `Db::new` spawns the purge background task via `tokio::spawn`, which
+    // requires a runtime to be running, so these need `#[tokio::test]`
+    // rather than a plain `#[test]`.
+
+    #[tokio::test]
+    async fn throttle_allows_burst_then_limits_then_refills() {
+        let db = Db::new();
+
+        // max_burst = 2, count_per_period = 1, period = 1s -> limit of 3
+        // requests, refilling one request per second.
+        for expected_remaining in [2, 1, 0] {
+            let result = db.throttle("key", 2, 1, 1, 1).unwrap();
+            assert!(!result.limited);
+            assert_eq!(result.remaining, expected_remaining);
+        }
+
+        let limited = db.throttle("key", 2, 1, 1, 1).unwrap();
+        assert!(limited.limited);
+        assert!(limited.retry_after > 0);
+
+        time::sleep(Duration::from_millis((limited.retry_after as u64) * 1000 + 50)).await;
+
+        let refilled = db.throttle("key", 2, 1, 1, 1).unwrap();
+        assert!(!refilled.limited);
+    }
+
+    #[tokio::test]
+    async fn throttle_rejects_zero_count_per_period() {
+        let db = Db::new();
+        assert!(db.throttle("key", 5, 0, 10, 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn throttle_rejects_overflowing_max_burst() {
+        let db = Db::new();
+        assert!(db.throttle("key", u64::MAX, 1, 10, 1).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+//! A minimal, idiomatic implementation of a Redis server.
+//!
+//! The purpose of this project is to provide a larger example of an
+//! idiomatic tokio application. Do not use this in production.
+
+pub mod cmd;
+pub use cmd::Command;
+
+pub mod connection;
+pub use connection::Connection;
+
+pub mod db;
+pub use db::Db;
+
+pub mod frame;
+pub use frame::Frame;
+
+pub(crate) mod parse;
+
+pub mod server;
+
+pub(crate) mod shutdown;
+pub(crate) use shutdown::Shutdown;
+
+/// Default port that the server listens on.
+///
+/// The `server` binary always binds to this port.
+pub const DEFAULT_PORT: u16 = 6379;
+
+/// Error returned by most functions.
+///
+/// When writing a real application, one might want to consider a specialized
+/// error handling crate or defining an error type as an `enum` of causes.
+/// However, for our example, using a boxed `std::error::Error` is sufficient.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// A specialized `Result` type for this crate.
+pub type Result<T> = std::result::Result<T, Error>;
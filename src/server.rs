@@ -0,0 +1,150 @@
+//! The server's accept loop and per-connection request handling.
+
+use crate::db::DbDropGuard;
+use crate::{Command, Connection, Db, Frame, Shutdown};
+
+use std::future::Future;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+
+/// Run the server, accepting connections from `listener` until `shutdown`
+/// resolves.
+///
+/// `shutdown` is typically [`tokio::signal::ctrl_c`]. Once it resolves, the
+/// accept loop stops taking new connections and every in-flight `Handler` is
+/// notified to wind down via its `Shutdown`.
+pub async fn run(listener: TcpListener, shutdown: impl Future) -> crate::Result<()> {
+    // Holds the `Db` and shuts down its purge task when dropped, i.e. once
+    // `run` returns, even if connection handlers still hold clones of it.
+    let db_holder = DbDropGuard::new();
+
+    // Sending on this channel, or dropping it, notifies every subscribed
+    // `Shutdown` that the server is shutting down.
+    let (notify_shutdown, _) = broadcast::channel(1);
+
+    tokio::select! {
+        res = accept(listener, db_holder.db(), &notify_shutdown) => {
+            // Only returns on an error accepting a connection; a connection
+            // erroring out on its own does not stop the accept loop.
+            res?;
+        }
+        _ = shutdown => {}
+    }
+
+    Ok(())
+}
+
+/// Accept inbound connections, spawning a `Handler` for each on its own
+/// task.
+async fn accept(
+    listener: TcpListener,
+    db: Db,
+    notify_shutdown: &broadcast::Sender<()>,
+) -> crate::Result<()> {
+    loop {
+        let (socket, _) = listener.accept().await?;
+
+        let mut handler = Handler::new(
+            db.clone(),
+            socket,
+            Shutdown::new(notify_shutdown.subscribe()),
+        );
+
+        tokio::spawn(async move {
+            let _ = handler.run().await;
+        });
+    }
+}
+
+/// Per-connection handler. Reads commands off the socket and applies them
+/// against the shared `Db`.
+#[derive(Debug)]
+pub(crate) struct Handler {
+    /// Shared database handle.
+    db: Db,
+
+    /// The TCP connection decorated with the Redis protocol encoder/decoder.
+    connection: Connection,
+
+    /// Listens for the server shutdown signal.
+    shutdown: Shutdown,
+}
+
+impl Handler {
+    /// Create a new `Handler` for a freshly accepted connection.
+    pub(crate) fn new(db: Db, socket: TcpStream, shutdown: Shutdown) -> Handler {
+        Handler {
+            db,
+            connection: Connection::new(socket),
+            shutdown,
+        }
+    }
+
+    /// Process the connection until the client disconnects or the server
+    /// begins shutting down.
+    ///
+    /// Clients commonly pipeline several commands back-to-back without
+    /// waiting for each reply, so every frame already buffered is drained
+    /// and applied, in arrival order, against the same `Db` before the
+    /// accumulated replies are flushed to the socket as a single write. A
+    /// command that fails to parse produces an error reply at its position
+    /// without aborting the rest of the pipeline.
+    ///
+    /// `SUBSCRIBE` takes over the connection for as long as the client
+    /// remains subscribed, reading further input directly off the socket
+    /// instead of returning to this loop. Commands are therefore parsed one
+    /// frame at a time rather than as a whole batch up front: if a `SUBSCRIBE`
+    /// is encountered, the remaining, not-yet-parsed frames from this batch
+    /// are handed to it so they are still processed, in order, instead of
+    /// being stranded and silently dropped.
+    pub(crate) async fn run(&mut self) -> crate::Result<()> {
+        while !self.shutdown.is_shutdown() {
+            let maybe_batch = tokio::select! {
+                res = self.connection.read_frame_batch() => res?,
+                _ = self.shutdown.recv() => return Ok(()),
+            };
+
+            let mut frames = match maybe_batch {
+                Some(frames) => frames.into_iter(),
+                // The peer closed the socket.
+                None => return Ok(()),
+            };
+
+            while let Some(frame) = frames.next() {
+                match Command::from_frame(frame) {
+                    Ok(Command::Subscribe(subscribe)) => {
+                        let pending = frames.by_ref().collect();
+
+                        self.connection.flush().await?;
+                        subscribe
+                            .apply(
+                                &self.db,
+                                &mut self.connection,
+                                &mut self.shutdown.resubscribe(),
+                                pending,
+                            )
+                            .await?;
+
+                        // `apply` has consumed the rest of this batch.
+                        break;
+                    }
+                    Ok(command) => {
+                        // Each dispatched command gets its own `Shutdown`,
+                        // since `Command::apply` takes one by value.
+                        command
+                            .apply(&self.db, &mut self.connection, self.shutdown.resubscribe())
+                            .await?;
+                    }
+                    Err(err) => {
+                        let response = Frame::Error(err.to_string());
+                        self.connection.write_frame(&response).await?;
+                    }
+                }
+            }
+
+            self.connection.flush().await?;
+        }
+
+        Ok(())
+    }
+}